@@ -0,0 +1,101 @@
+//! A `serde_json` formatter and writer for Matrix's canonical JSON encoding.
+
+use std::io;
+
+use serde::Serialize;
+use serde_json::ser::Formatter;
+
+use super::{CanonicalJsonValue, Error};
+
+/// The [`serde_json::ser::Formatter`] used to emit Matrix's canonical JSON encoding: UTF-8, no
+/// insignificant whitespace, and (thanks to `CanonicalJsonValue::Object` being backed by a
+/// `BTreeMap`) object keys in lexicographic order.
+///
+/// This currently relies entirely on the default `Formatter` methods, which already produce
+/// compact output with no extra whitespace. It's a named type of its own, rather than reusing
+/// [`serde_json::ser::CompactFormatter`] directly, so canonicalization has a single place to hook
+/// into if the escaping or separator rules the spec mandates ever need to diverge from
+/// serde_json's compact output.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CanonicalFormatter;
+
+impl CanonicalFormatter {
+    /// Create a new `CanonicalFormatter`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Formatter for CanonicalFormatter {}
+
+/// Serialize a `CanonicalJsonValue` into its canonical string representation.
+pub fn to_canonical_string(value: &CanonicalJsonValue) -> Result<String, Error> {
+    let bytes = to_canonical_vec(value)?;
+
+    // `CanonicalFormatter` never writes anything but well-formed UTF-8 JSON text.
+    Ok(String::from_utf8(bytes).expect("canonical JSON output is always valid UTF-8"))
+}
+
+/// Serialize a `CanonicalJsonValue` as canonical JSON directly into an `io::Write`, without
+/// allocating an intermediate `String`.
+pub fn to_canonical_writer<W: io::Write>(
+    writer: W,
+    value: &CanonicalJsonValue,
+) -> Result<(), Error> {
+    let mut serializer = serde_json::Serializer::with_formatter(writer, CanonicalFormatter::new());
+    value.serialize(&mut serializer).map_err(Error::Json)
+}
+
+fn to_canonical_vec(value: &CanonicalJsonValue) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::with_capacity(128);
+    to_canonical_writer(&mut bytes, value)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::TryFrom, io};
+
+    use serde_json::json;
+
+    use super::{to_canonical_string, to_canonical_writer};
+    use crate::canonical_json::{CanonicalJsonValue, Error};
+
+    #[test]
+    fn produces_sorted_whitespace_free_output() {
+        let value = CanonicalJsonValue::try_from(json!({
+            "b": 1,
+            "a": "two",
+            "c": [true, null],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            to_canonical_string(&value).unwrap(),
+            r#"{"a":"two","b":1,"c":[true,null]}"#
+        );
+    }
+
+    /// A writer that always fails, to exercise `to_canonical_writer`'s error path.
+    struct FailingWriter;
+
+    impl io::Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::Other, "write failed"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writer_failure_surfaces_as_json_error() {
+        let value = CanonicalJsonValue::try_from(json!({ "a": 1 })).unwrap();
+
+        assert!(matches!(
+            to_canonical_writer(FailingWriter, &value),
+            Err(Error::Json(_))
+        ));
+    }
+}
@@ -0,0 +1,53 @@
+//! De-/serialization helpers for the Matrix canonical JSON format.
+
+use std::collections::BTreeMap;
+
+mod formatter;
+mod raw;
+mod value;
+
+pub use self::{
+    formatter::{to_canonical_string, to_canonical_writer, CanonicalFormatter},
+    raw::RawCanonicalValue,
+    value::CanonicalJsonValue,
+};
+
+/// The set of keys and values that make up a JSON object.
+pub type CanonicalJsonObject = BTreeMap<String, CanonicalJsonValue>;
+
+/// An error that occurs when converting to or from `CanonicalJsonValue`.
+#[derive(Debug)]
+pub enum Error {
+    /// The `serde_json::Number` was not an integer in the range `[-(2^53)+1, (2^53)-1]` allowed
+    /// by the canonical JSON specification.
+    IntConvert,
+
+    /// The `serde_json::Number` had a fractional part, an exponent, or was `NaN`/`Infinity`.
+    ///
+    /// Canonical JSON only ever represents integers; floating-point numbers are not allowed.
+    FloatNotAllowed,
+
+    /// The underlying JSON text was not well-formed, or serializing to it failed.
+    Json(serde_json::Error),
+
+    /// The JSON value was nested more deeply than the configured recursion-depth limit allows.
+    DepthLimitExceeded,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IntConvert => write!(f, "number out of allowed range for canonical JSON"),
+            Self::FloatNotAllowed => {
+                write!(
+                    f,
+                    "floating-point numbers are not allowed in canonical JSON"
+                )
+            }
+            Self::Json(err) => write!(f, "malformed JSON: {}", err),
+            Self::DepthLimitExceeded => write!(f, "JSON value exceeded the maximum nesting depth"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,124 @@
+use std::{borrow::Cow, convert::TryInto, fmt};
+
+use serde::{
+    de::{self, Deserializer, Visitor},
+    ser::Serializer,
+    Deserialize, Serialize,
+};
+
+use super::{CanonicalJsonValue, Error};
+
+/// The sentinel struct name serde_json's (de)serializer recognizes to pass raw, unparsed JSON
+/// text through a newtype wrapper instead of walking it token by token. This is the same
+/// mechanism `serde_json::value::RawValue` itself is built on.
+const TOKEN: &str = "$serde_json::private::RawValue";
+
+/// A borrowed slice of JSON text that is already known to be in canonical form.
+///
+/// Unlike [`CanonicalJsonValue`], this does not build a tree of owned values; it just remembers
+/// the byte span of the original input. This avoids the cost of reparsing large events that are
+/// only going to be hashed, signed, or re-emitted verbatim, and not inspected or mutated. Call
+/// [`parse`](Self::parse) to materialize a full `CanonicalJsonValue` when one is needed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawCanonicalValue<'a>(Cow<'a, str>);
+
+impl<'a> RawCanonicalValue<'a> {
+    /// Wrap a slice of JSON text that the caller has already canonicalized.
+    ///
+    /// This performs no parsing or validation; passing text that isn't actually in canonical
+    /// form will silently propagate through signing and hashing.
+    pub fn from_canonical_str(canonical_json: impl Into<Cow<'a, str>>) -> Self {
+        Self(canonical_json.into())
+    }
+
+    /// The raw canonical JSON text backing this value.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Parse this value into an owned [`CanonicalJsonValue`] tree.
+    ///
+    /// Named `parse` rather than `to_owned` so it isn't mistaken for the infallible,
+    /// type-preserving clone that `ToOwned` gives every `Clone` type for free; this is a real
+    /// JSON parse that can fail and that changes type.
+    pub fn parse(&self) -> Result<CanonicalJsonValue, Error> {
+        serde_json::from_str::<serde_json::Value>(&self.0)
+            .map_err(Error::Json)?
+            .try_into()
+    }
+}
+
+impl<'a> fmt::Display for RawCanonicalValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'a> Serialize for RawCanonicalValue<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(TOKEN, self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for RawCanonicalValue<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RawCanonicalValueVisitor;
+
+        impl<'de> Visitor<'de> for RawCanonicalValueVisitor {
+            type Value = RawCanonicalValue<'de>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("any valid JSON value, captured as raw text")
+            }
+
+            fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(RawCanonicalValue(Cow::Borrowed(v)))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(RawCanonicalValue(Cow::Owned(v.to_owned())))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TOKEN, RawCanonicalValueVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RawCanonicalValue;
+    use crate::canonical_json::CanonicalJsonValue;
+
+    #[test]
+    fn round_trips_through_serialize_and_deserialize() {
+        let raw = RawCanonicalValue::from_canonical_str(r#"{"a":1,"b":[true,null]}"#);
+
+        let serialized = serde_json::to_string(&raw).unwrap();
+        let deserialized: RawCanonicalValue<'_> = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(deserialized.as_str(), raw.as_str());
+    }
+
+    #[test]
+    fn parse_materializes_the_equivalent_value() {
+        let raw = RawCanonicalValue::from_canonical_str(r#"{"a":1,"b":[true,null]}"#);
+        let parsed = raw.parse().unwrap();
+
+        let expected =
+            CanonicalJsonValue::try_from_json_value(serde_json::json!({"a": 1, "b": [true, null]}))
+                .unwrap();
+        assert_eq!(parsed, expected);
+    }
+}
@@ -6,7 +6,7 @@ use std::{
 
 use js_int::Int;
 use serde::{de::Deserializer, ser::Serializer, Deserialize, Serialize};
-use serde_json::{to_string as to_json_string, Value as JsonValue};
+use serde_json::Value as JsonValue;
 
 use super::Error;
 
@@ -111,31 +111,90 @@ impl fmt::Display for CanonicalJsonValue {
     /// assert_eq!(compact,
     ///     "{\"city\":\"London\",\"street\":\"10 Downing Street\"}");
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", to_json_string(&self).map_err(|_| fmt::Error)?)
+        write!(
+            f,
+            "{}",
+            super::to_canonical_string(self).map_err(|_| fmt::Error)?
+        )
     }
 }
 
+/// The default maximum nesting depth allowed when converting a `serde_json::Value` into a
+/// `CanonicalJsonValue`.
+///
+/// This bounds the recursion depth of the conversion, protecting against maliciously deep
+/// federation payloads that would otherwise be able to overflow the stack.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+impl CanonicalJsonValue {
+    /// Convert a `serde_json::Value` into canonical form, enforcing `DEFAULT_MAX_DEPTH`.
+    pub fn try_from_json_value(json: JsonValue) -> Result<Self, Error> {
+        Self::try_from_json_value_with_limit(json, DEFAULT_MAX_DEPTH)
+    }
+
+    /// Convert a `serde_json::Value` into canonical form, enforcing a custom maximum nesting
+    /// depth.
+    ///
+    /// Pass `usize::MAX` for `max_depth` to effectively disable the limit, e.g. when the input is
+    /// already known to come from a trusted source.
+    pub fn try_from_json_value_with_limit(
+        json: JsonValue,
+        max_depth: usize,
+    ) -> Result<Self, Error> {
+        try_from_json_value(json, max_depth, 0)
+    }
+}
+
+fn try_from_json_value(
+    json: JsonValue,
+    max_depth: usize,
+    depth: usize,
+) -> Result<CanonicalJsonValue, Error> {
+    if depth > max_depth {
+        return Err(Error::DepthLimitExceeded);
+    }
+
+    Ok(match json {
+        JsonValue::Bool(b) => CanonicalJsonValue::Bool(b),
+        JsonValue::Number(num) => {
+            // `is_f64` is true whenever the number was parsed with a fractional part or an
+            // exponent (or, if constructed directly, from any `f64`, even an integral one). Canonical
+            // JSON has no representation for floats, so reject it rather than lossily
+            // truncating it to an integer.
+            if let Some(f) = num.as_f64() {
+                if num.is_f64() || !f.is_finite() {
+                    return Err(Error::FloatNotAllowed);
+                }
+            }
+
+            let int = match num.as_i64() {
+                Some(int) => int,
+                None => i64::try_from(num.as_u64().ok_or(Error::IntConvert)?)
+                    .map_err(|_| Error::IntConvert)?,
+            };
+
+            CanonicalJsonValue::Integer(Int::try_from(int).map_err(|_| Error::IntConvert)?)
+        }
+        JsonValue::Array(vec) => CanonicalJsonValue::Array(
+            vec.into_iter()
+                .map(|v| try_from_json_value(v, max_depth, depth + 1))
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        JsonValue::String(string) => CanonicalJsonValue::String(string),
+        JsonValue::Object(obj) => CanonicalJsonValue::Object(
+            obj.into_iter()
+                .map(|(k, v)| Ok((k, try_from_json_value(v, max_depth, depth + 1)?)))
+                .collect::<Result<BTreeMap<_, _>, _>>()?,
+        ),
+        JsonValue::Null => CanonicalJsonValue::Null,
+    })
+}
+
 impl TryFrom<JsonValue> for CanonicalJsonValue {
     type Error = Error;
 
     fn try_from(json: JsonValue) -> Result<Self, Self::Error> {
-        Ok(match json {
-            JsonValue::Bool(b) => Self::Bool(b),
-            JsonValue::Number(num) => Self::Integer(
-                Int::try_from(num.as_i64().ok_or(Error::IntConvert)?)
-                    .map_err(|_| Error::IntConvert)?,
-            ),
-            JsonValue::Array(vec) => {
-                Self::Array(vec.into_iter().map(TryInto::try_into).collect::<Result<Vec<_>, _>>()?)
-            }
-            JsonValue::String(string) => Self::String(string),
-            JsonValue::Object(obj) => Self::Object(
-                obj.into_iter()
-                    .map(|(k, v)| Ok((k, v.try_into()?)))
-                    .collect::<Result<BTreeMap<_, _>, _>>()?,
-            ),
-            JsonValue::Null => Self::Null,
-        })
+        Self::try_from_json_value(json)
     }
 }
 
@@ -173,3 +232,85 @@ impl<'de> Deserialize<'de> for CanonicalJsonValue {
         Ok(val.try_into().map_err(serde::de::Error::custom)?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use serde_json::json;
+
+    use super::{CanonicalJsonValue, Error, JsonValue, DEFAULT_MAX_DEPTH};
+
+    #[test]
+    fn rejects_fractional_numbers() {
+        assert!(matches!(
+            CanonicalJsonValue::try_from(json!(1.0)),
+            Err(Error::FloatNotAllowed)
+        ));
+    }
+
+    #[test]
+    fn rejects_exponent_notation() {
+        let value: JsonValue = serde_json::from_str("1e1").unwrap();
+        assert!(matches!(
+            CanonicalJsonValue::try_from(value),
+            Err(Error::FloatNotAllowed)
+        ));
+    }
+
+    #[test]
+    fn rejects_exponents_that_overflow_to_infinity() {
+        // `1e400` overflows `f64`. serde_json refuses to parse such a number at all, so it is
+        // rejected before it ever reaches the conversion into `CanonicalJsonValue`.
+        let result = serde_json::from_str::<CanonicalJsonValue>("1e400");
+        assert!(result.unwrap_err().to_string().contains("number out of range"));
+    }
+
+    #[test]
+    fn accepts_js_safe_integers() {
+        assert!(CanonicalJsonValue::try_from(json!(9_007_199_254_740_991i64)).is_ok());
+        // 2^53 - 1
+    }
+
+    #[test]
+    fn rejects_integers_outside_js_safe_range() {
+        let value = json!(9_007_199_254_740_992i64); // 2^53
+        assert!(matches!(
+            CanonicalJsonValue::try_from(value),
+            Err(Error::IntConvert)
+        ));
+    }
+
+    #[test]
+    fn rejects_u64_values_too_large_for_i64() {
+        assert!(matches!(
+            CanonicalJsonValue::try_from(json!(u64::MAX)),
+            Err(Error::IntConvert)
+        ));
+    }
+
+    fn nested_arrays(depth: usize) -> JsonValue {
+        let mut value = json!(0);
+        for _ in 0..depth {
+            value = JsonValue::Array(vec![value]);
+        }
+        value
+    }
+
+    #[test]
+    fn accepts_depth_exactly_at_limit() {
+        let value = nested_arrays(DEFAULT_MAX_DEPTH);
+        assert!(
+            CanonicalJsonValue::try_from_json_value_with_limit(value, DEFAULT_MAX_DEPTH).is_ok()
+        );
+    }
+
+    #[test]
+    fn rejects_depth_over_limit() {
+        let value = nested_arrays(DEFAULT_MAX_DEPTH + 1);
+        assert!(matches!(
+            CanonicalJsonValue::try_from_json_value_with_limit(value, DEFAULT_MAX_DEPTH),
+            Err(Error::DepthLimitExceeded)
+        ));
+    }
+}
@@ -0,0 +1,11 @@
+//! Types and traits for (de)serialization of Matrix-related types.
+//!
+//! Most of this crate is extracted from serde_json and is useful for implementing the canonical
+//! JSON encoding used throughout the Matrix protocol specification: converting untyped JSON into
+//! a deterministic, byte-stable representation that can be signed and hashed.
+
+#![warn(missing_docs)]
+
+pub mod canonical_json;
+
+pub use canonical_json::CanonicalJsonValue;
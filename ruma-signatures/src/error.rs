@@ -0,0 +1,51 @@
+//! Error conditions.
+
+use std::fmt;
+
+/// An error encountered when signing, verifying, hashing, or redacting a JSON object.
+#[derive(Debug)]
+pub enum Error {
+    /// The object being signed, verified, hashed, or redacted was not a JSON object.
+    NotAnObject,
+
+    /// The key identifier (`algorithm:version`) is not valid.
+    InvalidKeyId(ruma_identifiers_validation::Error),
+
+    /// The secret or public key bytes were not a valid Ed25519 key.
+    InvalidKey(ed25519_dalek::SignatureError),
+
+    /// The object could not be re-canonicalized for signing or verification.
+    Canonicalize(ruma_serde::canonical_json::Error),
+
+    /// The stored signature was not validly base64-encoded.
+    Base64(base64::DecodeError),
+
+    /// The object has no `signatures` entry for the given server name and key identifier.
+    NotSigned,
+
+    /// The signature did not match the given public key.
+    Verification(ed25519_dalek::SignatureError),
+
+    /// The given room version is not a known, numbered Matrix room version, so its redaction
+    /// rules aren't implemented here.
+    UnsupportedRoomVersion(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAnObject => write!(f, "JSON value must be an object"),
+            Self::InvalidKeyId(err) => write!(f, "invalid key identifier: {}", err),
+            Self::InvalidKey(err) => write!(f, "invalid Ed25519 key: {}", err),
+            Self::Canonicalize(err) => write!(f, "could not canonicalize JSON: {}", err),
+            Self::Base64(err) => write!(f, "invalid base64 in signature: {}", err),
+            Self::NotSigned => write!(f, "object is not signed with the given key"),
+            Self::Verification(err) => write!(f, "signature verification failed: {}", err),
+            Self::UnsupportedRoomVersion(version) => {
+                write!(f, "unsupported room version: {}", version)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
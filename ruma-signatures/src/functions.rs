@@ -0,0 +1,175 @@
+//! Functions for signing and verifying JSON.
+
+use std::{collections::BTreeMap, convert::TryFrom};
+
+use base64::{decode_config, encode_config, STANDARD_NO_PAD};
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+use ruma_serde::canonical_json::{CanonicalJsonObject, CanonicalJsonValue};
+
+use crate::{Error, KeyPair};
+
+/// Remove the given top-level keys from a JSON object before it is signed, hashed, or verified.
+///
+/// Matrix excludes these keys because they are themselves derived from (or attached alongside)
+/// the rest of the object, so folding them into the thing they sign would be circular.
+pub(crate) fn strip_keys(object: &CanonicalJsonObject, keys: &[&str]) -> CanonicalJsonObject {
+    object
+        .iter()
+        .filter(|(key, _)| !keys.contains(&key.as_str()))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+/// Serialize a JSON object through the canonical JSON encoding, for signing or hashing.
+pub(crate) fn canonical_bytes(object: CanonicalJsonObject) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    ruma_serde::canonical_json::to_canonical_writer(
+        &mut bytes,
+        &CanonicalJsonValue::Object(object),
+    )
+    .map_err(Error::Canonicalize)?;
+    Ok(bytes)
+}
+
+fn object_mut<'a>(
+    value: &'a mut CanonicalJsonValue,
+) -> Result<&'a mut BTreeMap<String, CanonicalJsonValue>, Error> {
+    match value {
+        CanonicalJsonValue::Object(map) => Ok(map),
+        _ => Err(Error::NotAnObject),
+    }
+}
+
+/// Sign a JSON object with the given key pair, inserting the resulting signature at
+/// `signatures[server_name][key_id]`.
+///
+/// Any existing `signatures` entry is preserved and merged into: other servers' signatures, and
+/// other key identifiers already present for `server_name`, are left untouched. A signature
+/// already stored under the same `server_name` and `key_id` is overwritten.
+pub fn sign_json<K>(
+    server_name: &str,
+    key_pair: &K,
+    object: &mut CanonicalJsonObject,
+) -> Result<(), Error>
+where
+    K: KeyPair,
+{
+    let key_id = format!("{}:{}", key_pair.algorithm(), key_pair.version());
+    ruma_identifiers_validation::device_key_id::validate(&key_id).map_err(Error::InvalidKeyId)?;
+
+    let unsigned = strip_keys(object, &["signatures", "unsigned"]);
+    let signature = encode_config(key_pair.sign(&canonical_bytes(unsigned)?), STANDARD_NO_PAD);
+
+    let signatures = object
+        .entry("signatures".to_owned())
+        .or_insert_with(|| CanonicalJsonValue::Object(BTreeMap::new()));
+    let server_signatures = object_mut(
+        object_mut(signatures)?
+            .entry(server_name.to_owned())
+            .or_insert_with(|| CanonicalJsonValue::Object(BTreeMap::new())),
+    )?;
+
+    server_signatures.insert(key_id, CanonicalJsonValue::String(signature));
+
+    Ok(())
+}
+
+/// Verify a signed JSON object against the given Ed25519 public key.
+///
+/// Returns `Ok(())` if `object` carries a valid signature from `server_name` under `key_id`,
+/// and an error describing why verification failed otherwise.
+pub fn verify_json(
+    public_key: &[u8],
+    server_name: &str,
+    key_id: &str,
+    object: &CanonicalJsonObject,
+) -> Result<(), Error> {
+    ruma_identifiers_validation::device_key_id::validate(key_id).map_err(Error::InvalidKeyId)?;
+
+    let signature_b64 = match object.get("signatures") {
+        Some(CanonicalJsonValue::Object(servers)) => match servers.get(server_name) {
+            Some(CanonicalJsonValue::Object(keys)) => match keys.get(key_id) {
+                Some(CanonicalJsonValue::String(sig)) => sig,
+                _ => return Err(Error::NotSigned),
+            },
+            _ => return Err(Error::NotSigned),
+        },
+        _ => return Err(Error::NotSigned),
+    };
+
+    let signature_bytes = decode_config(signature_b64, STANDARD_NO_PAD).map_err(Error::Base64)?;
+    let signature =
+        Signature::try_from(signature_bytes.as_slice()).map_err(Error::Verification)?;
+    let public_key = PublicKey::from_bytes(public_key).map_err(Error::InvalidKey)?;
+
+    let unsigned = strip_keys(object, &["signatures", "unsigned"]);
+    public_key
+        .verify(&canonical_bytes(unsigned)?, &signature)
+        .map_err(Error::Verification)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use ruma_serde::canonical_json::{CanonicalJsonObject, CanonicalJsonValue};
+    use serde_json::json;
+
+    use super::{sign_json, verify_json};
+    use crate::{Ed25519KeyPair, Error, KeyPair};
+
+    fn test_key_pair() -> Ed25519KeyPair {
+        Ed25519KeyPair::new(&[1u8; 32], "1".to_owned()).unwrap()
+    }
+
+    fn test_object() -> CanonicalJsonObject {
+        let value = CanonicalJsonValue::try_from(json!({
+            "city": "London",
+            "street": "10 Downing Street",
+        }))
+        .unwrap();
+        match value {
+            CanonicalJsonValue::Object(object) => object,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn sign_and_verify_round_trip() {
+        let key_pair = test_key_pair();
+        let mut object = test_object();
+
+        sign_json("example.org", &key_pair, &mut object).unwrap();
+
+        let result = verify_json(key_pair.public_key(), "example.org", "ed25519:1", &object);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let key_pair = test_key_pair();
+        let mut object = test_object();
+
+        sign_json("example.org", &key_pair, &mut object).unwrap();
+        object.insert(
+            "city".to_owned(),
+            CanonicalJsonValue::String("Berlin".to_owned()),
+        );
+
+        assert!(matches!(
+            verify_json(key_pair.public_key(), "example.org", "ed25519:1", &object),
+            Err(Error::Verification(_))
+        ));
+    }
+
+    #[test]
+    fn verify_without_signature_fails() {
+        let key_pair = test_key_pair();
+        let object = test_object();
+
+        assert!(matches!(
+            verify_json(key_pair.public_key(), "example.org", "ed25519:1", &object),
+            Err(Error::NotSigned)
+        ));
+    }
+}
@@ -0,0 +1,58 @@
+//! Key pairs used to sign JSON objects.
+
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signature, Signer};
+
+use crate::Error;
+
+/// A cryptographic key pair that can sign byte slices.
+pub trait KeyPair {
+    /// The name of the signing algorithm, e.g. `ed25519`.
+    fn algorithm(&self) -> &str;
+
+    /// The version component of this key pair's key identifier, e.g. `1`.
+    fn version(&self) -> &str;
+
+    /// Sign a byte slice and return the resulting signature.
+    fn sign(&self, message: &[u8]) -> Vec<u8>;
+}
+
+/// An Ed25519 key pair, the only signing algorithm currently used by Matrix.
+pub struct Ed25519KeyPair {
+    keypair: Keypair,
+    version: String,
+}
+
+impl Ed25519KeyPair {
+    /// Create a new Ed25519 key pair from a raw secret key and a version.
+    ///
+    /// `secret_key` must be the raw 32-byte Ed25519 secret key seed, not base64-encoded; decode it
+    /// with `base64::decode_config` first if it came from a homeserver's `signing.key` file.
+    pub fn new(secret_key: &[u8], version: String) -> Result<Self, Error> {
+        let secret = SecretKey::from_bytes(secret_key).map_err(Error::InvalidKey)?;
+        let public = PublicKey::from(&secret);
+        Ok(Self {
+            keypair: Keypair { secret, public },
+            version,
+        })
+    }
+
+    /// The public key half of this key pair.
+    pub fn public_key(&self) -> &[u8] {
+        self.keypair.public.as_bytes()
+    }
+}
+
+impl KeyPair for Ed25519KeyPair {
+    fn algorithm(&self) -> &str {
+        "ed25519"
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn sign(&self, message: &[u8]) -> Vec<u8> {
+        let signature: Signature = self.keypair.sign(message);
+        signature.to_bytes().to_vec()
+    }
+}
@@ -0,0 +1,18 @@
+//! Digital signatures and verification for the Matrix federation protocol.
+//!
+//! Matrix signs and verifies JSON objects (events, requests between homeservers) rather than
+//! raw bytes. A canonical JSON representation ([`ruma_serde::CanonicalJsonValue`]) makes this
+//! possible: the same object always serializes to the same bytes, on every homeserver, so a
+//! signature produced here can be checked anywhere else.
+
+#![warn(missing_docs)]
+
+mod error;
+mod functions;
+mod keys;
+mod redaction;
+
+pub use error::Error;
+pub use functions::{sign_json, verify_json};
+pub use keys::{Ed25519KeyPair, KeyPair};
+pub use redaction::{content_hash, content_hash_raw, redact};
@@ -0,0 +1,280 @@
+//! Computing an event's reference content hash and its redacted form.
+
+use std::collections::BTreeMap;
+
+use base64::{encode_config, STANDARD_NO_PAD};
+use ruma_serde::canonical_json::{CanonicalJsonValue, RawCanonicalValue};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    functions::{canonical_bytes, strip_keys},
+    Error,
+};
+
+/// Top-level keys that survive redaction for every event, regardless of type.
+const ALLOWED_KEYS: &[&str] = &[
+    "event_id",
+    "type",
+    "room_id",
+    "sender",
+    "state_key",
+    "content",
+    "hashes",
+    "signatures",
+    "depth",
+    "prev_events",
+    "auth_events",
+    "origin",
+    "origin_server_ts",
+];
+
+/// Which `content` keys a redacted event keeps, for a specific event type and room version.
+enum AllowedContent {
+    /// Keep the listed keys, in addition to whatever the top-level allow-list already keeps.
+    Keys(&'static [&'static str]),
+
+    /// Keep `content` entirely, unredacted.
+    All,
+}
+
+/// Determine which `content` keys survive redaction for `event_type` in room version `version`,
+/// per the per-room-version redaction rules in the Matrix rooms specification.
+fn allowed_content_keys(event_type: &str, version: u8) -> AllowedContent {
+    use AllowedContent::Keys;
+
+    match event_type {
+        "m.room.member" => Keys(&["membership"]),
+        // Room version 11 stopped redacting `m.room.create` content entirely.
+        "m.room.create" if version >= 11 => AllowedContent::All,
+        "m.room.create" => Keys(&["creator"]),
+        // Room version 9 added `join_authorised_via_users_server` for restricted joins.
+        "m.room.join_rules" if version >= 9 => {
+            Keys(&["join_rule", "join_authorised_via_users_server"])
+        }
+        "m.room.join_rules" => Keys(&["join_rule"]),
+        "m.room.power_levels" => Keys(&[
+            "ban",
+            "events",
+            "events_default",
+            "kick",
+            "redact",
+            "state_default",
+            "users",
+            "users_default",
+        ]),
+        // Room version 7 stopped preserving `aliases` content on redaction.
+        "m.room.aliases" if version >= 7 => Keys(&[]),
+        "m.room.aliases" => Keys(&["aliases"]),
+        "m.room.history_visibility" => Keys(&["history_visibility"]),
+        _ => Keys(&[]),
+    }
+}
+
+/// Compute the reference (content) hash of a JSON object, as stored in `hashes.sha256`.
+///
+/// The returned value is the unpadded base64 encoding of the SHA-256 digest of the object's
+/// canonical JSON form, after removing `unsigned`, `signatures`, and `hashes`.
+pub fn content_hash(value: &CanonicalJsonValue) -> Result<String, Error> {
+    let object = match value {
+        CanonicalJsonValue::Object(object) => object,
+        _ => return Err(Error::NotAnObject),
+    };
+
+    let without_metadata = strip_keys(object, &["unsigned", "signatures", "hashes"]);
+    let digest = Sha256::digest(&canonical_bytes(without_metadata)?);
+
+    Ok(encode_config(digest, STANDARD_NO_PAD))
+}
+
+/// Compute the reference content hash of JSON text that a caller already knows is canonical and
+/// already has `unsigned`, `signatures`, and `hashes` stripped out.
+///
+/// This hashes `raw`'s bytes directly, skipping the parse into an owned [`CanonicalJsonValue`]
+/// tree that [`content_hash`] has to do. Use it for large, already-stripped event bytes coming
+/// straight off the wire; reach for [`content_hash`] whenever the `unsigned`/`signatures`/`hashes`
+/// keys still need to be removed, since that requires walking the object anyway.
+pub fn content_hash_raw(raw: &RawCanonicalValue<'_>) -> String {
+    let digest = Sha256::digest(raw.as_str().as_bytes());
+    encode_config(digest, STANDARD_NO_PAD)
+}
+
+/// Produce the redacted form of a JSON object, keeping only the keys the given room version's
+/// redaction algorithm allows.
+///
+/// `room_version` is the numbered Matrix room version ID the event belongs to (e.g. `"10"`).
+/// The top-level allow-list is the same for every room version; which `content` keys survive
+/// depends on both the event's `type` and `room_version`, per the room version's redaction rules
+/// (for example, `m.room.aliases` content stopped surviving redaction from version 7 onward).
+/// Returns [`Error::UnsupportedRoomVersion`] if `room_version` isn't a recognized numbered
+/// version.
+pub fn redact(value: CanonicalJsonValue, room_version: &str) -> Result<CanonicalJsonValue, Error> {
+    let version: u8 = room_version
+        .parse()
+        .map_err(|_| Error::UnsupportedRoomVersion(room_version.to_owned()))?;
+
+    let object = match value {
+        CanonicalJsonValue::Object(object) => object,
+        _ => return Err(Error::NotAnObject),
+    };
+
+    let event_type = match object.get("type") {
+        Some(CanonicalJsonValue::String(event_type)) => event_type.clone(),
+        _ => String::new(),
+    };
+
+    let mut redacted: BTreeMap<String, CanonicalJsonValue> = object
+        .into_iter()
+        .filter(|(key, _)| ALLOWED_KEYS.contains(&key.as_str()))
+        .collect();
+
+    if let Some(CanonicalJsonValue::Object(content)) = redacted.get("content") {
+        let redacted_content = match allowed_content_keys(&event_type, version) {
+            AllowedContent::All => content.clone(),
+            AllowedContent::Keys(allowed) => content
+                .iter()
+                .filter(|(key, _)| allowed.contains(&key.as_str()))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        };
+        redacted.insert(
+            "content".to_owned(),
+            CanonicalJsonValue::Object(redacted_content),
+        );
+    }
+
+    Ok(CanonicalJsonValue::Object(redacted))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use ruma_serde::canonical_json::{CanonicalJsonValue, RawCanonicalValue};
+    use serde_json::json;
+
+    use super::{content_hash, content_hash_raw, redact};
+    use crate::Error;
+
+    fn event(event_type: &str, content: serde_json::Value) -> CanonicalJsonValue {
+        CanonicalJsonValue::try_from(json!({
+            "content": content,
+            "room_id": "!room:example.org",
+            "sender": "@alice:example.org",
+            "type": event_type,
+        }))
+        .unwrap()
+    }
+
+    /// The keys of the redacted event's `content`, for asserting on.
+    fn content_keys(redacted: &CanonicalJsonValue) -> Vec<&str> {
+        match redacted {
+            CanonicalJsonValue::Object(object) => match object.get("content") {
+                Some(CanonicalJsonValue::Object(content)) => {
+                    content.keys().map(String::as_str).collect()
+                }
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn content_hash_known_answer() {
+        let value = CanonicalJsonValue::try_from(json!({
+            "content": { "body": "Hello World", "msgtype": "m.text" },
+            "type": "m.room.message",
+            "room_id": "!room:example.org",
+            "sender": "@alice:example.org",
+            "unsigned": { "age": 1234 },
+            "signatures": { "example.org": { "ed25519:1": "some_signature" } },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            content_hash(&value).unwrap(),
+            "QaZNhDtba4tDtv4DifpWyO/SaCqRQa8i3axC9Cpa6Bo"
+        );
+    }
+
+    #[test]
+    fn content_hash_raw_matches_content_hash() {
+        let value = CanonicalJsonValue::try_from(json!({
+            "content": { "body": "Hello World", "msgtype": "m.text" },
+            "type": "m.room.message",
+            "room_id": "!room:example.org",
+            "sender": "@alice:example.org",
+            "unsigned": { "age": 1234 },
+            "signatures": { "example.org": { "ed25519:1": "some_signature" } },
+        }))
+        .unwrap();
+
+        let object = match &value {
+            CanonicalJsonValue::Object(object) => object,
+            _ => unreachable!(),
+        };
+        let without_metadata =
+            crate::functions::strip_keys(object, &["unsigned", "signatures", "hashes"]);
+        let canonical = ruma_serde::canonical_json::to_canonical_string(
+            &CanonicalJsonValue::Object(without_metadata),
+        )
+        .unwrap();
+        let raw = RawCanonicalValue::from_canonical_str(canonical);
+
+        assert_eq!(content_hash_raw(&raw), content_hash(&value).unwrap());
+    }
+
+    #[test]
+    fn aliases_redaction_depends_on_room_version() {
+        let value = event(
+            "m.room.aliases",
+            json!({ "aliases": ["#room:example.org"] }),
+        );
+
+        let redacted_v6 = redact(value.clone(), "6").unwrap();
+        let redacted_v7 = redact(value, "7").unwrap();
+
+        assert!(content_keys(&redacted_v6).contains(&"aliases"));
+        assert!(!content_keys(&redacted_v7).contains(&"aliases"));
+    }
+
+    #[test]
+    fn join_rules_redaction_depends_on_room_version() {
+        let value = event(
+            "m.room.join_rules",
+            json!({
+                "join_rule": "restricted",
+                "join_authorised_via_users_server": "@alice:example.org",
+            }),
+        );
+
+        let redacted_v8 = redact(value.clone(), "8").unwrap();
+        let redacted_v9 = redact(value, "9").unwrap();
+
+        assert!(!content_keys(&redacted_v8).contains(&"join_authorised_via_users_server"));
+        assert!(content_keys(&redacted_v9).contains(&"join_authorised_via_users_server"));
+    }
+
+    #[test]
+    fn create_redaction_depends_on_room_version() {
+        let value = event(
+            "m.room.create",
+            json!({ "creator": "@alice:example.org", "room_version": "11" }),
+        );
+
+        let redacted_v10 = redact(value.clone(), "10").unwrap();
+        let redacted_v11 = redact(value, "11").unwrap();
+
+        assert!(!content_keys(&redacted_v10).contains(&"room_version"));
+        assert!(content_keys(&redacted_v11).contains(&"room_version"));
+    }
+
+    #[test]
+    fn unsupported_room_version_is_rejected() {
+        let value = event("m.room.message", json!({}));
+
+        assert!(matches!(
+            redact(value, "not-a-version"),
+            Err(Error::UnsupportedRoomVersion(version)) if version == "not-a-version"
+        ));
+    }
+}